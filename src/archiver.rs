@@ -0,0 +1,234 @@
+use crate::error::WorkReportError;
+use crate::query;
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A single work report loaded from disk, keyed by the date encoded in its file name.
+pub struct Report {
+    pub date: NaiveDate,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+impl Report {
+    fn load(path: PathBuf) -> Result<Option<Report>, WorkReportError> {
+        let date = match query::parse_report_date(&path) {
+            Some(date) => date,
+            None => return Ok(None),
+        };
+        let content = fs::read_to_string(&path)
+            .map_err(|e| WorkReportError::io(path.to_string_lossy(), "read file", e))?;
+        Ok(Some(Report { date, path, content }))
+    }
+}
+
+/// Loads every report in `paths`, skipping any whose name doesn't encode a date.
+pub(crate) fn load_reports(paths: Vec<PathBuf>) -> Result<Vec<Report>, WorkReportError> {
+    let mut reports = Vec::with_capacity(paths.len());
+    for path in paths {
+        if let Some(report) = Report::load(path)? {
+            reports.push(report);
+        }
+    }
+    Ok(reports)
+}
+
+/// Output format selectable for [`WorkReportGenerator::export`](crate::WorkReportGenerator::export).
+pub enum Format {
+    /// The current raw-copy behavior: reports concatenated in date order, unchanged.
+    Raw,
+    /// A single Markdown digest, one section per day.
+    Markdown,
+    /// A browsable HTML monthly calendar with each day's report sections inlined.
+    Html,
+}
+
+impl Format {
+    pub(crate) fn archiver(&self) -> Box<dyn Archiver> {
+        match self {
+            Format::Raw => Box::new(RawArchiver),
+            Format::Markdown => Box::new(MarkdownArchiver),
+            Format::Html => Box::new(HtmlCalendarArchiver),
+        }
+    }
+}
+
+/// Renders a set of reports into a single exportable document.
+pub trait Archiver {
+    fn render(&self, reports: &[Report]) -> String;
+}
+
+/// The current raw-copy behavior: reports concatenated in date order, unchanged.
+pub struct RawArchiver;
+
+impl Archiver for RawArchiver {
+    fn render(&self, reports: &[Report]) -> String {
+        reports
+            .iter()
+            .map(|r| r.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Combines reports into a single Markdown digest, one `##` section per day.
+pub struct MarkdownArchiver;
+
+impl Archiver for MarkdownArchiver {
+    fn render(&self, reports: &[Report]) -> String {
+        let mut out = String::new();
+        for report in reports {
+            out.push_str(&format!("## {}\n\n", report.date.format("%Y-%m-%d")));
+            out.push_str(&report.content);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Lays out a browsable HTML monthly calendar, one grid per month, inlining the
+/// `<Today's task>`/`<TODO>` sections into the cell of each day that has a report.
+pub struct HtmlCalendarArchiver;
+
+impl Archiver for HtmlCalendarArchiver {
+    fn render(&self, reports: &[Report]) -> String {
+        let mut months: BTreeMap<(i32, u32), BTreeMap<u32, &Report>> = BTreeMap::new();
+        for report in reports {
+            months
+                .entry((report.date.year(), report.date.month()))
+                .or_default()
+                .insert(report.date.day(), report);
+        }
+
+        let mut html = String::from("<html>\n<body>\n");
+        for ((year, month), days) in &months {
+            html.push_str(&format!("<h2>{}-{:02}</h2>\n<table>\n<tr>", year, month));
+            for weekday in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] {
+                html.push_str(&format!("<th>{}</th>", weekday));
+            }
+            html.push_str("</tr>\n<tr>");
+
+            let first = NaiveDate::from_ymd_opt(*year, *month, 1).expect("month is always valid");
+            let mut column = first.weekday().num_days_from_monday();
+            for _ in 0..column {
+                html.push_str("<td></td>");
+            }
+            for day in 1..=days_in_month(*year, *month) {
+                if column == 7 {
+                    html.push_str("</tr>\n<tr>");
+                    column = 0;
+                }
+                match days.get(&day) {
+                    Some(report) => html.push_str(&format!(
+                        "<td><strong>{}</strong>{}</td>",
+                        day,
+                        render_sections(report)
+                    )),
+                    None => html.push_str(&format!("<td>{}</td>", day)),
+                }
+                column += 1;
+            }
+            html.push_str("</tr>\n</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .expect("month is always valid");
+    (next_month - NaiveDate::from_ymd_opt(year, month, 1).expect("month is always valid")).num_days() as u32
+}
+
+/// Pulls the `<Today's task>` and `<TODO>` sections out of a report's content for inlining.
+fn render_sections(report: &Report) -> String {
+    format!(
+        "<div class=\"task\"><pre>{}</pre></div><div class=\"todo\"><pre>{}</pre></div>",
+        html_escape(&extract_section(&report.content, "<Today's task>")),
+        html_escape(&extract_section(&report.content, "<TODO>")),
+    )
+}
+
+/// Returns the body of `heading` up to (but not including) the next `<...>` heading.
+pub(crate) fn extract_section(content: &str, heading: &str) -> String {
+    let mut section = String::new();
+    let mut in_section = false;
+    for line in content.lines() {
+        if line.trim() == heading {
+            in_section = true;
+            continue;
+        }
+        if in_section {
+            if line.trim().starts_with('<') {
+                break;
+            }
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+    section
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn report(date: &str, content: &str) -> Report {
+        Report {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            path: PathBuf::from(format!("{}.txt", date.replace('-', ""))),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_extract_section_stops_at_next_heading() {
+        let content = "<Today's task>\n- did the thing\n\n<TODO>\n- finish the report\n-\n";
+        assert_eq!(
+            extract_section(content, "<Today's task>"),
+            "- did the thing\n\n"
+        );
+        assert_eq!(
+            extract_section(content, "<TODO>"),
+            "- finish the report\n-\n"
+        );
+    }
+
+    #[test]
+    fn test_extract_section_missing_heading_is_empty() {
+        assert_eq!(extract_section("no headings here", "<TODO>"), "");
+    }
+
+    #[test]
+    fn test_raw_archiver_concatenates_content() {
+        let reports = vec![report("2020-08-24", "first"), report("2020-08-25", "second")];
+        assert_eq!(RawArchiver.render(&reports), "first\nsecond");
+    }
+
+    #[test]
+    fn test_markdown_archiver_adds_a_heading_per_day() {
+        let reports = vec![report("2020-08-24", "did stuff\n")];
+        assert_eq!(
+            MarkdownArchiver.render(&reports),
+            "## 2020-08-24\n\ndid stuff\n\n"
+        );
+    }
+
+    #[test]
+    fn test_days_in_month() {
+        assert_eq!(days_in_month(2020, 2), 29);
+        assert_eq!(days_in_month(2021, 2), 28);
+        assert_eq!(days_in_month(2020, 12), 31);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::error::WorkReportError;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+/// Computes the SHA-256 digest of `content`, hex-encoded.
+pub(crate) fn content_hash(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Archives `content` (already read from the report named `root`) by content address:
+/// stores it once under `Archive/objects/<hash>` and hard-links `dst_path` to that blob,
+/// skipping the work if `dst_path` already holds matching content (whether or not it
+/// is a link to the blob).
+///
+/// Sharp edge: `dst_path` is a hard link, not a copy, so every date that happens to
+/// share a blob is the *same inode*. Editing an archived `YYYYmmdd.txt` in place
+/// mutates that blob and therefore every other date hardlinked to it too. Treat
+/// archived files as read-only under dedup mode; if you need to edit one, write to a
+/// fresh copy rather than in place.
+pub(crate) fn archive(
+    exec_dir: &str,
+    root: &str,
+    content: &[u8],
+    dst_path: &str,
+) -> Result<(), WorkReportError> {
+    let hash = content_hash(content);
+    let objects_dir = format!("{}/Archive/objects", exec_dir);
+    fs::create_dir_all(&objects_dir).map_err(|e| WorkReportError::io(&objects_dir, "create directory", e))?;
+    let blob_path = format!("{}/{}", objects_dir, hash);
+
+    if !Path::new(&blob_path).exists() {
+        fs::write(&blob_path, content).map_err(|e| WorkReportError::io(&blob_path, "write file", e))?;
+    }
+
+    if Path::new(dst_path).exists() {
+        let existing = fs::read(dst_path).map_err(|e| WorkReportError::io(dst_path, "read file", e))?;
+        if content_hash(&existing) == hash {
+            return Ok(());
+        }
+        fs::remove_file(dst_path).map_err(|e| WorkReportError::io(dst_path, "remove file", e))?;
+    }
+
+    fs::hard_link(&blob_path, dst_path).map_err(|e| WorkReportError::io(dst_path, "hard link file", e))?;
+    println!("    Archived (deduped): {}", root);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn test_content_hash_is_stable_and_content_sensitive() {
+        let a = content_hash(b"hello");
+        let b = content_hash(b"hello");
+        let c = content_hash(b"world");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_archive_same_content_shares_one_blob_via_hardlink() {
+        let exec_dir = std::env::temp_dir().join(format!(
+            "work_report_dedup_test_{:?}",
+            std::thread::current().id()
+        ));
+        fs::create_dir_all(&exec_dir).unwrap();
+
+        let dst_a = exec_dir.join("20200824.txt");
+        let dst_b = exec_dir.join("20200825.txt");
+        let content = b"<Today's task>\n-\n";
+
+        archive(
+            exec_dir.to_str().unwrap(),
+            "20200824.txt",
+            content,
+            dst_a.to_str().unwrap(),
+        )
+        .unwrap();
+        archive(
+            exec_dir.to_str().unwrap(),
+            "20200825.txt",
+            content,
+            dst_b.to_str().unwrap(),
+        )
+        .unwrap();
+
+        let objects_dir = exec_dir.join("Archive").join("objects");
+        let blob_count = fs::read_dir(&objects_dir).unwrap().count();
+        assert_eq!(blob_count, 1, "identical content should share a single blob");
+
+        let meta_a = fs::metadata(&dst_a).unwrap();
+        let meta_b = fs::metadata(&dst_b).unwrap();
+        assert_eq!(
+            meta_a.ino(),
+            meta_b.ino(),
+            "both dates should hard-link to the same inode"
+        );
+
+        fs::remove_dir_all(&exec_dir).unwrap();
+    }
+}
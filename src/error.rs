@@ -0,0 +1,53 @@
+use std::fmt;
+
+/// Errors produced by [`WorkReportGenerator`](crate::WorkReportGenerator).
+#[derive(Debug)]
+pub enum WorkReportError {
+    /// An I/O operation on `path` failed while performing `operation`.
+    Io {
+        path: String,
+        operation: &'static str,
+        source: std::io::Error,
+    },
+    /// A report file name did not match the expected `YYYYmmdd.txt` pattern.
+    InvalidFileName(String),
+    /// `init` refused to overwrite the template that already exists at `path`.
+    TemplateAlreadyExists(String),
+}
+
+impl fmt::Display for WorkReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkReportError::Io {
+                path,
+                operation,
+                source,
+            } => write!(f, "failed to {} `{}`: {}", operation, path, source),
+            WorkReportError::InvalidFileName(name) => {
+                write!(f, "invalid report file name: `{}`", name)
+            }
+            WorkReportError::TemplateAlreadyExists(path) => {
+                write!(f, "refusing to overwrite existing template: `{}`", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WorkReportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorkReportError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl WorkReportError {
+    pub(crate) fn io(path: impl Into<String>, operation: &'static str, source: std::io::Error) -> Self {
+        WorkReportError::Io {
+            path: path.into(),
+            operation,
+            source,
+        }
+    }
+}
@@ -1,10 +1,22 @@
-use chrono::Local;
+mod archiver;
+mod dedup;
+mod error;
+mod query;
+mod template;
+
+use chrono::{Datelike, Local, NaiveDate};
 use glob::glob;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use regex::Regex;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+pub use archiver::{Archiver, Format, HtmlCalendarArchiver, MarkdownArchiver, RawArchiver, Report};
+pub use error::WorkReportError;
 
 /// Generator to create daily work reports.
 ///
@@ -14,21 +26,23 @@ use std::path::Path;
 /// extern crate work_report;
 /// use work_report::WorkReportGenerator;
 ///
+/// std::fs::create_dir_all("./work_report").unwrap();
 /// let generator = WorkReportGenerator::new("./work_report");
 ///
 /// // Create a new work report.
 /// // Argument `date` must be in the format of `YYYYmmdd`.
-/// generator.create_new("20200101");
+/// generator.create_new("20200101").unwrap();
 /// // Create today's work report.
-/// generator.create_for_today();
+/// generator.create_for_today().unwrap();
 ///
 /// // Archive a specific file.
-/// generator.archive("./work_report/20200101.txt");
+/// generator.archive("./work_report/20200101.txt").unwrap();
 /// // Archive all the files.
-/// generator.archive_all();
+/// generator.archive_all().unwrap();
 /// ```
 pub struct WorkReportGenerator {
     exec_dir: String,
+    dedup: bool,
 }
 
 /// Core methods.
@@ -39,9 +53,19 @@ impl WorkReportGenerator {
     pub fn new(dir: impl Into<String>) -> WorkReportGenerator {
         WorkReportGenerator {
             exec_dir: dir.into(),
+            dedup: false,
         }
     }
 
+    /// Enable content-addressed deduplication: archived files are stored once under
+    /// `Archive/objects/<hash>` and the per-date `Archive/YYYY/MM/YYYYmmdd.txt` entry
+    /// becomes a hard link to that blob, so archiving many near-identical daily reports
+    /// doesn't repeatedly store the same bytes.
+    pub fn with_dedup(mut self, enabled: bool) -> Self {
+        self.dedup = enabled;
+        self
+    }
+
     /// Archive all the work reports in the current directory by year and month in separate directoryies.
     ///
     /// ## Examples
@@ -49,24 +73,32 @@ impl WorkReportGenerator {
     /// `./20200826.txt`
     /// is copied to
     /// `./Archive/2020/08/20200826.txt`
-    pub fn archive_all(&self) {
+    pub fn archive_all(&self) -> Result<(), WorkReportError> {
         println!("Archiving...");
         // "YYYYmmdd.txt"のパターンにマッチするテキストファイルのみをアーカイブする
-        let re = Regex::new(r"\d{8}.txt$").unwrap();
-        for entry in glob(&format!("{}/*.txt", self.exec_dir))
-            .expect("cannot get the contents of the directory.")
-        {
+        let re = Regex::new(r"\d{8}(_weekly)?.txt$").unwrap();
+        let entries = glob(&format!("{}/*.txt", self.exec_dir)).map_err(|e| {
+            WorkReportError::io(&self.exec_dir, "read directory", std::io::Error::other(e))
+        })?;
+        for entry in entries {
             // 無効なパスは無視する
             let path_ = match entry {
                 Ok(path) => path,
                 Err(_) => continue,
             };
-            let path = path_.to_str().unwrap();
+            let path = match path_.to_str() {
+                Some(p) => p,
+                None => continue,
+            };
             if re.is_match(path) {
-                self.archive(&path);
+                // 1つのファイルのアーカイブに失敗しても，残りのファイルの処理は続ける
+                if let Err(e) = self.archive(path) {
+                    println!("    Skipped {}: {}", path, e);
+                }
             }
         }
         println!("All the files have been archived.");
+        Ok(())
     }
 
     /// Archive the work report.
@@ -81,26 +113,36 @@ impl WorkReportGenerator {
     /// `./20200826.txt`
     /// is copied to
     /// `./Archive/2020/08/20200826.txt`
-    pub fn archive(&self, src_path: &str) {
+    pub fn archive(&self, src_path: &str) -> Result<(), WorkReportError> {
         // ファイル名を切り取り，アーカイブ先までの途中のディレクトリを作成する
-        let root = Path::new(src_path).file_name().unwrap().to_str().unwrap();
-        let partial_path_for_archive = generate_partial_path_for_archive_dir(root);
+        let root = Path::new(src_path)
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or_else(|| WorkReportError::InvalidFileName(src_path.to_string()))?;
+        let partial_path_for_archive = generate_partial_path_for_archive_dir(root)?;
         let dst_dir = format!("{}/{}", self.exec_dir, partial_path_for_archive);
-        fs::create_dir_all(&dst_dir).expect("cannot create the directory");
+        fs::create_dir_all(&dst_dir).map_err(|e| WorkReportError::io(&dst_dir, "create directory", e))?;
 
         let dst_path: String = format!("{}/{}", dst_dir, root);
+
+        if self.dedup {
+            let content = fs::read(src_path).map_err(|e| WorkReportError::io(src_path, "read file", e))?;
+            return dedup::archive(&self.exec_dir, root, &content, &dst_path);
+        }
+
         let archived = Path::new(&dst_path).exists();
         // 未アーカイブか，ファイルの内容が更新されていれば，コピーする
-        if !archived || updated(&src_path, &dst_path) {
-            fs::copy(&src_path, &dst_path).expect("cannot copy the file.");
+        if !archived || updated(src_path, &dst_path)? {
+            fs::copy(src_path, &dst_path).map_err(|e| WorkReportError::io(src_path, "copy file", e))?;
             println!("    Archived: {}", &root);
         }
+        Ok(())
     }
 
     /// Create today's work report based on `Template.txt` in the same directory as the execution file.
-    pub fn create_for_today(&self) {
-        let today = Local::today().format("%Y%m%d").to_string();
-        self.create_new(today);
+    pub fn create_for_today(&self) -> Result<(), WorkReportError> {
+        let today = Local::now().date_naive().format("%Y%m%d").to_string();
+        self.create_new(today)
     }
 
     /// Create a new work report.
@@ -111,26 +153,159 @@ impl WorkReportGenerator {
     /// ```bash
     /// $ cp Template date.txt
     /// ```
-    pub fn create_new(&self, date: impl Into<String>) {
-        let dst_filename = format!("{}/{}.txt", self.exec_dir, date.into());
+    ///
+    /// `Template.txt` may contain `{{date}}`, `{{weekday}}`, `{{iso_week}}`, and
+    /// `{{prev_report}}` tokens, which are expanded for the new report; a template with
+    /// no tokens is copied through unchanged.
+    pub fn create_new(&self, date: impl Into<String>) -> Result<(), WorkReportError> {
+        let date = date.into();
+        let dst_filename = format!("{}/{}.txt", self.exec_dir, date);
         let src_filename = format!("{}/Template.txt", self.exec_dir);
         if Path::new(&dst_filename).exists() {
             println!("Today's work report already exists.");
-            return;
+            return Ok(());
         }
         if !Path::new(&src_filename).exists() {
             println!("Template.txt was not found, so it is generated automatically.");
-            self.create_template();
+            self.create_template()?;
             println!("    Created: {}", &src_filename);
         }
-        fs::copy(&src_filename, &dst_filename).expect("cannot copy the file.");
+
+        let content = fs::read_to_string(&src_filename)
+            .map_err(|e| WorkReportError::io(&src_filename, "read file", e))?;
+        let rendered = match query::parse_report_date(Path::new(&dst_filename)) {
+            Some(report_date) => {
+                template::expand(&content, report_date, self.previous_todo(report_date).as_deref())
+            }
+            None => content,
+        };
+        fs::write(&dst_filename, rendered).map_err(|e| WorkReportError::io(&dst_filename, "write file", e))?;
         println!("    Created: {}.", &dst_filename);
+        Ok(())
+    }
+
+    /// Finds the most recent report strictly before `before` and returns its unfinished
+    /// `<TODO>` lines (blank lines and bare `-` bullets dropped), so only actual
+    /// carried-over tasks roll forward via the `{{prev_report}}` template token.
+    fn previous_todo(&self, before: NaiveDate) -> Option<String> {
+        let earliest = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+        let day_before = before.pred_opt()?;
+        let prev_path = self.reports_in_range(earliest, day_before).pop()?;
+        let content = fs::read_to_string(&prev_path).ok()?;
+        let todo = archiver::extract_section(&content, "<TODO>");
+        let unfinished = template::unfinished_todo_lines(&todo);
+        if unfinished.is_empty() {
+            None
+        } else {
+            Some(unfinished)
+        }
+    }
+
+    /// Find every report whose date falls within the inclusive `[start, end]` range,
+    /// searching both the flat `exec_dir` and the `Archive/YYYY/MM` tree.
+    pub fn reports_in_range(&self, start: NaiveDate, end: NaiveDate) -> Vec<PathBuf> {
+        query::reports_in_range(&self.exec_dir, start, end)
+    }
+
+    /// Create a weekly report, `YYYYmmdd_weekly.txt`, keyed to the Monday of the current week.
+    ///
+    /// [`archive`](Self::archive) and [`archive_all`](Self::archive_all) recognize the
+    /// `_weekly` suffix, so the file is archived into the same `Archive/YYYY/MM`
+    /// directory as daily reports.
+    pub fn create_for_week(&self) -> Result<(), WorkReportError> {
+        let today = Local::now().date_naive();
+        let monday = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        let date = monday.format("%Y%m%d").to_string();
+        self.create_new(format!("{}_weekly", date))
+    }
+
+    /// Scaffold a fresh work report directory: create `exec_dir` if it doesn't exist yet,
+    /// and write `Template.txt` if one isn't already there. Refuses to overwrite an
+    /// existing template, returning [`WorkReportError::TemplateAlreadyExists`] instead
+    /// of silently clobbering it.
+    pub fn init(&self) -> Result<(), WorkReportError> {
+        if !Path::new(&self.exec_dir).exists() {
+            fs::create_dir_all(&self.exec_dir)
+                .map_err(|e| WorkReportError::io(&self.exec_dir, "create directory", e))?;
+            println!("    Created: {}", &self.exec_dir);
+        }
+
+        let template_path = format!("{}/Template.txt", self.exec_dir);
+        if Path::new(&template_path).exists() {
+            return Err(WorkReportError::TemplateAlreadyExists(template_path));
+        }
+
+        self.create_template()?;
+        println!("Initialized work report directory: {}", &self.exec_dir);
+        println!("    Created: {}", &template_path);
+        Ok(())
+    }
+
+    /// Collect the reports in `[start, end]` and write a single rendering of them,
+    /// using the [`Archiver`] selected by `format`, to `dst`.
+    pub fn export(
+        &self,
+        format: Format,
+        start: NaiveDate,
+        end: NaiveDate,
+        dst: &Path,
+    ) -> Result<(), WorkReportError> {
+        let paths = self.reports_in_range(start, end);
+        let reports = archiver::load_reports(paths)?;
+        let rendered = format.archiver().render(&reports);
+        fs::write(dst, rendered).map_err(|e| WorkReportError::io(dst.to_string_lossy(), "write file", e))
+    }
+
+    /// Watch `exec_dir` and archive any `YYYYmmdd.txt` report the moment it is
+    /// created or modified, reusing the same hash check as [`archive`](Self::archive)
+    /// to skip redundant copies.
+    ///
+    /// This call blocks forever, so it is meant to be run as the sole action of a
+    /// long-running process (e.g. a background watch command), not mixed with
+    /// one-shot operations. Rapid successive writes to the same file (an editor
+    /// autosaving, for instance) are coalesced into a single event rather than
+    /// archived dozens of times.
+    pub fn watch(&self) -> Result<(), WorkReportError> {
+        // 短い間隔で連続する書き込みイベントをまとめてくれる，デバウンス付きのウォッチャー
+        const DEBOUNCE: Duration = Duration::from_secs(2);
+
+        let (tx, rx) = channel();
+        let mut watcher: RecommendedWatcher = Watcher::new(tx, DEBOUNCE).map_err(|e| {
+            WorkReportError::io(&self.exec_dir, "start file watcher", std::io::Error::other(e))
+        })?;
+        watcher
+            .watch(&self.exec_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| {
+                WorkReportError::io(&self.exec_dir, "watch directory", std::io::Error::other(e))
+            })?;
+
+        println!("Watching {} for changes...", &self.exec_dir);
+        let re = Regex::new(r"\d{8}(_weekly)?.txt$").unwrap();
+        loop {
+            match rx.recv() {
+                Ok(DebouncedEvent::Create(path)) | Ok(DebouncedEvent::Write(path)) => {
+                    let path_str = match path.to_str() {
+                        Some(p) => p,
+                        None => continue,
+                    };
+                    if re.is_match(path_str) {
+                        // 1つのファイルのアーカイブに失敗しても，監視は続ける
+                        if let Err(e) = self.archive(path_str) {
+                            println!("    Skipped {}: {}", path_str, e);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => println!("watch error: {:?}", e),
+            }
+        }
     }
 
     /// Create `Template.txt`
-    fn create_template(&self) {
+    fn create_template(&self) -> Result<(), WorkReportError> {
         let file_path = format!("{}/Template.txt", self.exec_dir);
-        let mut file = File::create(&file_path).unwrap();
+        let mut file =
+            File::create(&file_path).map_err(|e| WorkReportError::io(&file_path, "create file", e))?;
         // 自動生成されるTemplate.txtの中身
         let content = "\
 <Today's task>\n\
@@ -141,19 +316,19 @@ impl WorkReportGenerator {
 -\n\
 -\n\
 ";
-        writeln!(file, "{}", &content).unwrap();
-        file.sync_all().expect("failed to write out `Template.txt`");
+        writeln!(file, "{}", &content).map_err(|e| WorkReportError::io(&file_path, "write file", e))?;
+        file.sync_all()
+            .map_err(|e| WorkReportError::io(&file_path, "sync file", e))?;
+        Ok(())
     }
 }
 
 /// Check whether the file has been updated by comparing with the previously archived file by hash value.
-fn updated(path1: &str, path2: &str) -> bool {
-    let content1 = fs::read_to_string(&path1).expect("cannot read the file.");
-    let content2 = fs::read_to_string(&path2).expect("cannot read the file.");
-    let hash1 = md5::compute(content1);
-    let hash2 = md5::compute(content2);
+fn updated(path1: &str, path2: &str) -> Result<bool, WorkReportError> {
+    let content1 = fs::read(path1).map_err(|e| WorkReportError::io(path1, "read file", e))?;
+    let content2 = fs::read(path2).map_err(|e| WorkReportError::io(path2, "read file", e))?;
     // ハッシュ値が異なる = ファイルが更新されている
-    hash1 != hash2
+    Ok(dedup::content_hash(&content1) != dedup::content_hash(&content2))
 }
 
 /// Generates the partial path to the archive directory.
@@ -161,27 +336,23 @@ fn updated(path1: &str, path2: &str) -> bool {
 /// ## Examples
 ///
 /// `20200826.txt` -> `Archive/2020/08`
-fn generate_partial_path_for_archive_dir(file_name: &str) -> String {
+fn generate_partial_path_for_archive_dir(file_name: &str) -> Result<String, WorkReportError> {
     // ファイル名から年，月，日をそれぞれ取り出すための正規表現
     let re = Regex::new(
         r"(?x)
         (?P<Y>\d{4})
         (?P<m>\d{2})
         (?P<d>\d{2})
+        (_weekly)?
         .txt",
     )
     .unwrap();
-    let caps = re.captures(file_name).unwrap();
-    let year = caps
-        .name("Y")
-        .expect("did not match the regular expression.")
-        .as_str();
-    let month = caps
-        .name("m")
-        .expect("did not match the regular expression.")
-        .as_str();
-    let partial_path = format!("Archive/{}/{}", year, month);
-    partial_path
+    let caps = re
+        .captures(file_name)
+        .ok_or_else(|| WorkReportError::InvalidFileName(file_name.to_string()))?;
+    let year = &caps["Y"];
+    let month = &caps["m"];
+    Ok(format!("Archive/{}/{}", year, month))
 }
 
 #[cfg(test)]
@@ -193,7 +364,7 @@ mod test {
         fn before_after(input: &str, expected_output: String) {
             println!("{} -> {}", input, expected_output);
             assert_eq!(
-                generate_partial_path_for_archive_dir(input),
+                generate_partial_path_for_archive_dir(input).unwrap(),
                 expected_output
             );
         }
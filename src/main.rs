@@ -10,6 +10,22 @@ fn main() {
     let exec_dir = exec_dir.to_str().unwrap();
 
     let generator = WorkReportGenerator::new(exec_dir);
-    generator.archive_all();
-    generator.create_for_today();
+
+    // `init` scaffolds a fresh work report directory instead of archiving/creating today's report.
+    if env::args().nth(1).as_deref() == Some("init") {
+        if let Err(e) = generator.init() {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Err(e) = generator.archive_all() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+    if let Err(e) = generator.create_for_today() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,80 @@
+use chrono::NaiveDate;
+use glob::glob;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Parses the date a report file name encodes, ignoring the `_weekly` suffix if present.
+///
+/// ## Examples
+///
+/// `20200826.txt` -> `2020-08-26`
+/// `20200824_weekly.txt` -> `2020-08-24`
+pub(crate) fn parse_report_date(path: &Path) -> Option<NaiveDate> {
+    let stem = path.file_stem()?.to_str()?;
+    let date_part = stem.strip_suffix("_weekly").unwrap_or(stem);
+    NaiveDate::parse_from_str(date_part, "%Y%m%d").ok()
+}
+
+/// Finds every report under `exec_dir` (flat or already archived under `Archive/YYYY/MM`)
+/// whose date falls within the inclusive `[start, end]` range, sorted by date.
+///
+/// `archive` copies rather than moves, so a report that has already been archived
+/// exists in both locations with identical content; the two copies are deduplicated
+/// by file name here, keeping the archived one.
+pub fn reports_in_range(exec_dir: &str, start: NaiveDate, end: NaiveDate) -> Vec<PathBuf> {
+    // Archived copies are globbed first so `.or_insert` below keeps them over a
+    // same-named flat-directory duplicate.
+    let patterns = [
+        format!("{}/Archive/*/*/*.txt", exec_dir),
+        format!("{}/*.txt", exec_dir),
+    ];
+
+    let mut by_name: HashMap<String, (NaiveDate, PathBuf)> = HashMap::new();
+    for pattern in &patterns {
+        let entries = match glob(pattern) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for path in entries.filter_map(|entry| entry.ok()) {
+            let date = match parse_report_date(&path) {
+                Some(date) if start <= date && date <= end => date,
+                _ => continue,
+            };
+            let name = match path.file_name().and_then(|f| f.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            by_name.entry(name).or_insert((date, path));
+        }
+    }
+
+    let mut reports: Vec<(NaiveDate, PathBuf)> = by_name.into_values().collect();
+    reports.sort_by_key(|(date, _)| *date);
+    reports.into_iter().map(|(_, path)| path).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_report_date_daily() {
+        assert_eq!(
+            parse_report_date(Path::new("20200826.txt")),
+            NaiveDate::from_ymd_opt(2020, 8, 26)
+        );
+    }
+
+    #[test]
+    fn test_parse_report_date_weekly() {
+        assert_eq!(
+            parse_report_date(Path::new("/work_report/20200824_weekly.txt")),
+            NaiveDate::from_ymd_opt(2020, 8, 24)
+        );
+    }
+
+    #[test]
+    fn test_parse_report_date_non_date_file_name() {
+        assert_eq!(parse_report_date(Path::new("Template.txt")), None);
+    }
+}
@@ -0,0 +1,61 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Expands `{{date}}`, `{{weekday}}`, `{{iso_week}}`, and `{{prev_report}}` tokens in a
+/// report template. `prev_todo` is the carried-over unfinished `<TODO>` lines of the
+/// most recent prior report, if any. Templates with no tokens are returned unchanged.
+///
+/// `{{weekday}}` is always the English day name (chrono's `%A`); this crate does not
+/// depend on a locale-data crate, so no other locale is available.
+pub(crate) fn expand(template: &str, date: NaiveDate, prev_todo: Option<&str>) -> String {
+    if !template.contains("{{") {
+        return template.to_string();
+    }
+
+    template
+        .replace("{{date}}", &date.format("%Y-%m-%d").to_string())
+        .replace("{{weekday}}", &date.format("%A").to_string())
+        .replace("{{iso_week}}", &date.iso_week().week().to_string())
+        .replace("{{prev_report}}", prev_todo.unwrap_or(""))
+}
+
+/// Keeps only the unfinished lines of a `<TODO>` section body: drops blank lines and
+/// bare `-` bullets with no task text, so an empty checklist item doesn't roll forward.
+pub(crate) fn unfinished_todo_lines(section: &str) -> String {
+    section
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            !trimmed.is_empty() && trimmed != "-"
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expand_substitutes_tokens() {
+        let date = NaiveDate::from_ymd_opt(2020, 8, 24).unwrap();
+        let template = "{{date}} ({{weekday}}, week {{iso_week}})\n{{prev_report}}";
+        let rendered = expand(template, date, Some("- finish the report"));
+        assert_eq!(rendered, "2020-08-24 (Monday, week 35)\n- finish the report");
+    }
+
+    #[test]
+    fn test_expand_without_tokens_is_passthrough() {
+        let date = NaiveDate::from_ymd_opt(2020, 8, 24).unwrap();
+        let template = "<Today's task>\n-\n-\n\n<TODO>\n-\n-\n";
+        assert_eq!(expand(template, date, None), template);
+    }
+
+    #[test]
+    fn test_unfinished_todo_lines_drops_blank_and_bare_bullets() {
+        let section = "-\n- finish the report\n\n-\n- reply to email\n";
+        assert_eq!(
+            unfinished_todo_lines(section),
+            "- finish the report\n- reply to email"
+        );
+    }
+}